@@ -0,0 +1,589 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Control-flow graph construction and dominator tree computation over a
+//! function or program body.
+//!
+//! [`Cfg::build`] walks a function/program's statement list, splitting basic
+//! blocks at control-flow statements (`if`, loops, `switch`, `break`,
+//! `continue`, `return`, `throw`, `try`), and [`Cfg::dominators`] computes
+//! immediate dominators with the Cooper-Harvey-Kennedy iterative algorithm.
+//! Downstream analyses (dead-code detection, reachability, SSA-style passes)
+//! build on top of this.
+
+use crate::{GCLock, Node, NodeKind, NodeLabel, NodeVariant};
+use std::collections::HashMap;
+
+/// Index of a [`BasicBlock`] within a [`Cfg`].
+pub type BlockId = usize;
+
+/// A single basic block: a maximal run of statements with no internal
+/// control-flow split, plus the blocks that may execute immediately before
+/// and after it.
+#[derive(Debug, Default)]
+pub struct BasicBlock<'gc> {
+    /// Statements belonging to this block, in execution order.
+    pub statements: Vec<&'gc Node<'gc>>,
+    /// Blocks that may transfer control to this one.
+    pub predecessors: Vec<BlockId>,
+    /// Blocks this one may transfer control to.
+    pub successors: Vec<BlockId>,
+}
+
+/// The control-flow graph of a single function or program body.
+pub struct Cfg<'gc> {
+    pub blocks: Vec<BasicBlock<'gc>>,
+    pub entry: BlockId,
+    pub exit: BlockId,
+}
+
+/// The innermost enclosing loop/switch (for unlabeled `break`/`continue`) or
+/// `LabeledStatement` (for labeled ones), tracked while building the CFG.
+struct LoopCtx {
+    label: Option<NodeLabel>,
+    /// Block `break` (or falling off the end, for loops) jumps to.
+    break_target: BlockId,
+    /// Block `continue` jumps to (loops only; `None` for a bare `switch`).
+    continue_target: Option<BlockId>,
+}
+
+struct Builder<'gc> {
+    blocks: Vec<BasicBlock<'gc>>,
+    loop_stack: Vec<LoopCtx>,
+}
+
+impl<'gc> Builder<'gc> {
+    fn new_block(&mut self) -> BlockId {
+        self.blocks.push(BasicBlock::default());
+        self.blocks.len() - 1
+    }
+
+    fn link(&mut self, from: BlockId, to: BlockId) {
+        self.blocks[from].successors.push(to);
+        self.blocks[to].predecessors.push(from);
+    }
+
+    /// Emit a plain statement into `cur`, returning the block execution
+    /// continues in afterward (usually `cur` itself).
+    fn statement(&mut self, cur: BlockId, exit: BlockId, stmt: &'gc Node<'gc>) -> BlockId {
+        match stmt.variant() {
+            NodeVariant::IfStatement => self.if_statement(cur, exit, stmt),
+            NodeVariant::WhileStatement => self.while_statement(cur, exit, None, stmt),
+            NodeVariant::DoWhileStatement => self.do_while_statement(cur, exit, None, stmt),
+            NodeVariant::ForStatement => self.for_statement(cur, exit, None, stmt),
+            NodeVariant::SwitchStatement => self.switch_statement(cur, exit, None, stmt),
+            NodeVariant::BlockStatement => self.block(cur, exit, stmt),
+            NodeVariant::LabeledStatement => self.labeled_statement(cur, exit, stmt),
+            NodeVariant::BreakStatement => {
+                let target = self.resolve_jump_target(stmt, JumpKind::Break);
+                self.link(cur, target);
+                // Unreachable code after a break starts a fresh, disconnected block.
+                self.new_block()
+            }
+            NodeVariant::ContinueStatement => {
+                let target = self.resolve_jump_target(stmt, JumpKind::Continue);
+                self.link(cur, target);
+                self.new_block()
+            }
+            NodeVariant::ReturnStatement | NodeVariant::ThrowStatement => {
+                self.blocks[cur].statements.push(stmt);
+                self.link(cur, exit);
+                self.new_block()
+            }
+            NodeVariant::TryStatement => self.try_statement(cur, exit, stmt),
+            _ => {
+                self.blocks[cur].statements.push(stmt);
+                cur
+            }
+        }
+    }
+
+    fn statements(&mut self, mut cur: BlockId, exit: BlockId, body: crate::NodeList<'gc>) -> BlockId {
+        for stmt in body.iter() {
+            cur = self.statement(cur, exit, stmt);
+        }
+        cur
+    }
+
+    fn block(&mut self, cur: BlockId, exit: BlockId, node: &'gc Node<'gc>) -> BlockId {
+        self.statements(cur, exit, block_body(node))
+    }
+
+    fn if_statement(&mut self, cur: BlockId, exit: BlockId, node: &'gc Node<'gc>) -> BlockId {
+        let (test, consequent, alternate) = if_parts(node);
+        self.blocks[cur].statements.push(test);
+        let then_block = self.new_block();
+        self.link(cur, then_block);
+        let join = self.new_block();
+        let then_end = self.statement(then_block, exit, consequent);
+        self.link(then_end, join);
+        match alternate {
+            Some(alt) => {
+                let else_block = self.new_block();
+                self.link(cur, else_block);
+                let else_end = self.statement(else_block, exit, alt);
+                self.link(else_end, join);
+            }
+            None => self.link(cur, join),
+        }
+        join
+    }
+
+    fn while_statement(
+        &mut self,
+        cur: BlockId,
+        exit: BlockId,
+        label: Option<NodeLabel>,
+        node: &'gc Node<'gc>,
+    ) -> BlockId {
+        let (test, body) = loop_parts(node);
+        let header = self.new_block();
+        self.link(cur, header);
+        self.blocks[header].statements.push(test);
+        let after = self.new_block();
+        self.loop_stack.push(LoopCtx {
+            label,
+            break_target: after,
+            continue_target: Some(header),
+        });
+        let body_block = self.new_block();
+        self.link(header, body_block);
+        let body_end = self.statement(body_block, exit, body);
+        self.link(body_end, header);
+        self.link(header, after);
+        self.loop_stack.pop();
+        after
+    }
+
+    /// Unlike `while`, a `do`/`while` body runs unconditionally before the
+    /// test is ever checked, so the test sits at the *bottom* of the loop
+    /// (`body -> test -> body | after`) rather than gating entry into it —
+    /// there is no `header -> after` edge skipping the body.
+    fn do_while_statement(
+        &mut self,
+        cur: BlockId,
+        exit: BlockId,
+        label: Option<NodeLabel>,
+        node: &'gc Node<'gc>,
+    ) -> BlockId {
+        let (test, body) = loop_parts(node);
+        let body_block = self.new_block();
+        self.link(cur, body_block);
+        let test_block = self.new_block();
+        let after = self.new_block();
+        self.loop_stack.push(LoopCtx {
+            label,
+            break_target: after,
+            continue_target: Some(test_block),
+        });
+        let body_end = self.statement(body_block, exit, body);
+        self.link(body_end, test_block);
+        self.blocks[test_block].statements.push(test);
+        self.link(test_block, body_block);
+        self.link(test_block, after);
+        self.loop_stack.pop();
+        after
+    }
+
+    fn for_statement(
+        &mut self,
+        cur: BlockId,
+        exit: BlockId,
+        label: Option<NodeLabel>,
+        node: &'gc Node<'gc>,
+    ) -> BlockId {
+        let (init, test, update, body) = for_parts(node);
+        let mut cur = cur;
+        if let Some(init) = init {
+            self.blocks[cur].statements.push(init);
+        }
+        let header = self.new_block();
+        self.link(cur, header);
+        if let Some(test) = test {
+            self.blocks[header].statements.push(test);
+        }
+        let after = self.new_block();
+        let update_block = self.new_block();
+        if let Some(update) = update {
+            self.blocks[update_block].statements.push(update);
+        }
+        self.link(update_block, header);
+        self.loop_stack.push(LoopCtx {
+            label,
+            break_target: after,
+            continue_target: Some(update_block),
+        });
+        let body_block = self.new_block();
+        self.link(header, body_block);
+        let body_end = self.statement(body_block, exit, body);
+        self.link(body_end, update_block);
+        self.link(header, after);
+        self.loop_stack.pop();
+        after
+    }
+
+    fn switch_statement(
+        &mut self,
+        cur: BlockId,
+        exit: BlockId,
+        label: Option<NodeLabel>,
+        node: &'gc Node<'gc>,
+    ) -> BlockId {
+        let (discriminant, cases) = switch_parts(node);
+        self.blocks[cur].statements.push(discriminant);
+        let after = self.new_block();
+        self.loop_stack.push(LoopCtx {
+            label,
+            break_target: after,
+            continue_target: None,
+        });
+        let mut fallthrough = None;
+        for case in cases.iter() {
+            let case_block = self.new_block();
+            self.link(cur, case_block);
+            if let Some(prev_end) = fallthrough {
+                self.link(prev_end, case_block);
+            }
+            let end = self.statements(case_block, exit, case_body(case));
+            fallthrough = Some(end);
+        }
+        if let Some(end) = fallthrough {
+            self.link(end, after);
+        } else {
+            self.link(cur, after);
+        }
+        self.loop_stack.pop();
+        after
+    }
+
+    fn labeled_statement(&mut self, cur: BlockId, exit: BlockId, node: &'gc Node<'gc>) -> BlockId {
+        let (label, body) = labeled_parts(node);
+        // A label only changes how a `break`/`continue` naming it resolves;
+        // it doesn't introduce control flow of its own. So rather than
+        // pushing a separate synthetic frame around the body, apply the
+        // label directly to the `LoopCtx` the inner loop/switch pushes for
+        // itself — that's the only frame that actually knows the loop's real
+        // continue target, which a wrapping frame never has access to.
+        match body.variant() {
+            NodeVariant::WhileStatement => self.while_statement(cur, exit, Some(label), body),
+            NodeVariant::DoWhileStatement => self.do_while_statement(cur, exit, Some(label), body),
+            NodeVariant::ForStatement => self.for_statement(cur, exit, Some(label), body),
+            NodeVariant::SwitchStatement => self.switch_statement(cur, exit, Some(label), body),
+            _ => {
+                // A label on anything else only gives `break` somewhere to
+                // jump to; `continue` can't target it.
+                let after = self.new_block();
+                self.loop_stack.push(LoopCtx {
+                    label: Some(label),
+                    break_target: after,
+                    continue_target: None,
+                });
+                let end = self.statement(cur, exit, body);
+                self.loop_stack.pop();
+                self.link(end, after);
+                after
+            }
+        }
+    }
+
+    fn try_statement(&mut self, cur: BlockId, exit: BlockId, node: &'gc Node<'gc>) -> BlockId {
+        let (block, handler, finalizer) = try_parts(node);
+        let after = self.new_block();
+        let try_end = self.block(cur, exit, block);
+        let mut normal_exit = try_end;
+        if let Some(handler) = handler {
+            let handler_block = self.new_block();
+            // A throw anywhere in the try block is modeled by one edge from
+            // the block the try starts in; blocks created inside the try
+            // block by nested control flow have no edge to the handler.
+            self.link(cur, handler_block);
+            let handler_end = self.statement(handler_block, exit, handler);
+            let joined = self.new_block();
+            self.link(handler_end, joined);
+            self.link(normal_exit, joined);
+            normal_exit = joined;
+        }
+        match finalizer {
+            // The finalizer follows the normal exit of the try block (or of
+            // the handler). A `return`, `throw`, `break` or `continue` inside
+            // the try block or handler links straight to its target and does
+            // not pass through the finalizer.
+            Some(finalizer) => {
+                let finally_end = self.statement(normal_exit, exit, finalizer);
+                self.link(finally_end, after);
+            }
+            None => self.link(normal_exit, after),
+        }
+        after
+    }
+
+    fn resolve_jump_target(&self, node: &'gc Node<'gc>, kind: JumpKind) -> BlockId {
+        let label = jump_label(node);
+        let frame = match label {
+            Some(label) => self
+                .loop_stack
+                .iter()
+                .rev()
+                .find(|f| f.label == Some(label)),
+            None => self.loop_stack.iter().rev().find(|f| match kind {
+                JumpKind::Break => true,
+                JumpKind::Continue => f.continue_target.is_some(),
+            }),
+        }
+        .expect("break/continue must be inside a matching loop/switch/label");
+        match kind {
+            JumpKind::Break => frame.break_target,
+            JumpKind::Continue => frame.continue_target.unwrap_or(frame.break_target),
+        }
+    }
+}
+
+enum JumpKind {
+    Break,
+    Continue,
+}
+
+// The following accessors pull the fields this module needs out of each
+// statement kind by matching on `Node::kind()`, the same per-kind dispatch
+// that backs `Node::variant()` (used above in `statement()`).
+fn block_body<'gc>(node: &'gc Node<'gc>) -> crate::NodeList<'gc> {
+    match node.kind() {
+        NodeKind::BlockStatement(n) => n.body,
+        _ => panic!("block_body: expected BlockStatement, found {:?}", node.variant()),
+    }
+}
+fn if_parts<'gc>(
+    node: &'gc Node<'gc>,
+) -> (&'gc Node<'gc>, &'gc Node<'gc>, Option<&'gc Node<'gc>>) {
+    match node.kind() {
+        NodeKind::IfStatement(n) => (n.test, n.consequent, n.alternate),
+        _ => panic!("if_parts: expected IfStatement, found {:?}", node.variant()),
+    }
+}
+fn loop_parts<'gc>(node: &'gc Node<'gc>) -> (&'gc Node<'gc>, &'gc Node<'gc>) {
+    match node.kind() {
+        NodeKind::WhileStatement(n) => (n.test, n.body),
+        NodeKind::DoWhileStatement(n) => (n.test, n.body),
+        _ => panic!(
+            "loop_parts: expected WhileStatement/DoWhileStatement, found {:?}",
+            node.variant()
+        ),
+    }
+}
+fn for_parts<'gc>(
+    node: &'gc Node<'gc>,
+) -> (
+    Option<&'gc Node<'gc>>,
+    Option<&'gc Node<'gc>>,
+    Option<&'gc Node<'gc>>,
+    &'gc Node<'gc>,
+) {
+    match node.kind() {
+        NodeKind::ForStatement(n) => (n.init, n.test, n.update, n.body),
+        _ => panic!("for_parts: expected ForStatement, found {:?}", node.variant()),
+    }
+}
+fn switch_parts<'gc>(node: &'gc Node<'gc>) -> (&'gc Node<'gc>, crate::NodeList<'gc>) {
+    match node.kind() {
+        NodeKind::SwitchStatement(n) => (n.discriminant, n.cases),
+        _ => panic!("switch_parts: expected SwitchStatement, found {:?}", node.variant()),
+    }
+}
+fn case_body<'gc>(case: &'gc Node<'gc>) -> crate::NodeList<'gc> {
+    match case.kind() {
+        NodeKind::SwitchCase(n) => n.consequent,
+        _ => panic!("case_body: expected SwitchCase, found {:?}", case.variant()),
+    }
+}
+fn labeled_parts<'gc>(node: &'gc Node<'gc>) -> (NodeLabel, &'gc Node<'gc>) {
+    match node.kind() {
+        NodeKind::LabeledStatement(n) => (n.label, n.body),
+        _ => panic!("labeled_parts: expected LabeledStatement, found {:?}", node.variant()),
+    }
+}
+/// Extracts `TryStatement`'s `block`/`finalizer` directly, and unwraps its
+/// `handler` (a `CatchClause`) down to the `BlockStatement` it wraps, so that
+/// callers can feed it straight into [`Builder::statement`]/[`Builder::block`]
+/// like any other statement.
+fn try_parts<'gc>(
+    node: &'gc Node<'gc>,
+) -> (&'gc Node<'gc>, Option<&'gc Node<'gc>>, Option<&'gc Node<'gc>>) {
+    match node.kind() {
+        NodeKind::TryStatement(n) => (n.block, n.handler.map(catch_clause_body), n.finalizer),
+        _ => panic!("try_parts: expected TryStatement, found {:?}", node.variant()),
+    }
+}
+fn catch_clause_body<'gc>(node: &'gc Node<'gc>) -> &'gc Node<'gc> {
+    match node.kind() {
+        NodeKind::CatchClause(n) => n.body,
+        _ => panic!("catch_clause_body: expected CatchClause, found {:?}", node.variant()),
+    }
+}
+fn jump_label<'gc>(node: &'gc Node<'gc>) -> Option<NodeLabel> {
+    match node.kind() {
+        NodeKind::BreakStatement(n) => n.label,
+        NodeKind::ContinueStatement(n) => n.label,
+        _ => panic!(
+            "jump_label: expected BreakStatement/ContinueStatement, found {:?}",
+            node.variant()
+        ),
+    }
+}
+
+impl<'gc> Cfg<'gc> {
+    /// Build the control-flow graph for a function or program body.
+    pub fn build(_lock: &'gc GCLock, root: &'gc Node<'gc>) -> Cfg<'gc> {
+        let mut builder = Builder {
+            blocks: Vec::new(),
+            loop_stack: Vec::new(),
+        };
+        let entry = builder.new_block();
+        let exit = builder.new_block();
+        let body_end = builder.statements(entry, exit, function_body(root));
+        builder.link(body_end, exit);
+        Cfg {
+            blocks: builder.blocks,
+            entry,
+            exit,
+        }
+    }
+
+    /// Compute the immediate-dominator tree for this graph.
+    pub fn dominators(&self) -> Dominators {
+        Dominators::compute(self)
+    }
+}
+
+fn function_body<'gc>(root: &'gc Node<'gc>) -> crate::NodeList<'gc> {
+    match root.kind() {
+        NodeKind::Program(n) => n.body,
+        NodeKind::FunctionDeclaration(n) => block_body(n.body),
+        NodeKind::FunctionExpression(n) => block_body(n.body),
+        _ => panic!(
+            "function_body: expected Program/FunctionDeclaration/FunctionExpression, found {:?}",
+            root.variant()
+        ),
+    }
+}
+
+/// Immediate-dominator tree computed with the Cooper-Harvey-Kennedy
+/// iterative algorithm.
+pub struct Dominators {
+    /// `idom[b]` is `b`'s immediate dominator; `idom[entry] == entry`.
+    idom: Vec<BlockId>,
+    postorder_number: HashMap<BlockId, usize>,
+}
+
+impl Dominators {
+    fn compute(cfg: &Cfg) -> Dominators {
+        let postorder = postorder(cfg);
+        let postorder_number: HashMap<BlockId, usize> = postorder
+            .iter()
+            .enumerate()
+            .map(|(i, &b)| (b, i))
+            .collect();
+        let reverse_postorder: Vec<BlockId> = postorder.iter().rev().copied().collect();
+
+        let mut idom = vec![usize::MAX; cfg.blocks.len()];
+        idom[cfg.entry] = cfg.entry;
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &b in &reverse_postorder {
+                if b == cfg.entry {
+                    continue;
+                }
+                let mut new_idom = None;
+                for &p in &cfg.blocks[b].predecessors {
+                    if idom[p] == usize::MAX {
+                        continue;
+                    }
+                    new_idom = Some(match new_idom {
+                        None => p,
+                        Some(cur) => intersect(&idom, &postorder_number, cur, p),
+                    });
+                }
+                if let Some(new_idom) = new_idom {
+                    if idom[b] != new_idom {
+                        idom[b] = new_idom;
+                        changed = true;
+                    }
+                }
+            }
+        }
+
+        Dominators {
+            idom,
+            postorder_number,
+        }
+    }
+
+    /// Whether `a` dominates `b` (every path from the entry to `b` passes
+    /// through `a`), including `a == b`.
+    pub fn dominates(&self, a: BlockId, b: BlockId) -> bool {
+        let mut cur = b;
+        loop {
+            if cur == a {
+                return true;
+            }
+            let parent = self.idom[cur];
+            if parent == cur {
+                // Reached the entry without finding `a`.
+                return cur == a;
+            }
+            cur = parent;
+        }
+    }
+
+    /// The immediate dominator of `b`, or `b` itself if `b` is the entry.
+    pub fn immediate_dominator(&self, b: BlockId) -> BlockId {
+        self.idom[b]
+    }
+}
+
+/// Intersect two blocks' partially-known idom chains by walking both finger
+/// pointers up toward the entry (following `idom`) until they meet, using
+/// postorder numbers to decide which finger to advance (the one with the
+/// smaller postorder number is further from the entry).
+fn intersect(
+    idom: &[BlockId],
+    postorder_number: &HashMap<BlockId, usize>,
+    mut a: BlockId,
+    mut b: BlockId,
+) -> BlockId {
+    while a != b {
+        while postorder_number[&a] < postorder_number[&b] {
+            a = idom[a];
+        }
+        while postorder_number[&b] < postorder_number[&a] {
+            b = idom[b];
+        }
+    }
+    a
+}
+
+/// Reverse-postorder-friendly postorder traversal of the graph from `entry`.
+fn postorder(cfg: &Cfg) -> Vec<BlockId> {
+    let mut visited = vec![false; cfg.blocks.len()];
+    let mut order = Vec::with_capacity(cfg.blocks.len());
+    let mut stack: Vec<(BlockId, usize)> = vec![(cfg.entry, 0)];
+    visited[cfg.entry] = true;
+    while let Some(&mut (b, ref mut next)) = stack.last_mut() {
+        if let Some(&succ) = cfg.blocks[b].successors.get(*next) {
+            *next += 1;
+            if !visited[succ] {
+                visited[succ] = true;
+                stack.push((succ, 0));
+            }
+        } else {
+            order.push(b);
+            stack.pop();
+        }
+    }
+    order
+}
@@ -0,0 +1,77 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Non-aborting allocation paths for embedding Hermes in memory-constrained
+//! or sandboxed contexts, analogous to the `try_*` APIs in the
+//! fallible-collections ecosystem.
+//!
+//! The existing builders and [`NodeList::from_iter`] are infallible: if the
+//! GC heap can't satisfy an allocation, the process aborts. For a host that
+//! wants to set a hard heap cap on untrusted input and reject oversized ASTs
+//! instead of crashing, this module adds [`NodeList::try_from_iter`] and the
+//! primitive it is built on, [`GCLock::try_append_list_element`], which
+//! surface the failure as an [`AllocError`] instead. The node builders have
+//! no fallible counterpart here.
+
+use crate::context::{GCLock, NodeListElement};
+use crate::{Node, NodeList};
+use thiserror::Error;
+
+/// The GC heap could not satisfy a node allocation.
+#[derive(Debug, Copy, Clone, Error)]
+#[error("allocation failed: GC heap exhausted")]
+pub struct AllocError;
+
+impl<'ast, 'ctx> GCLock<'ast, 'ctx> {
+    /// Fallible counterpart to [`GCLock::append_list_element`]: attempts to
+    /// allocate a `NodeListElement` linking `node` on to `prev` (or heading a
+    /// fresh list if `prev` is `None`), returning `Err(AllocError)` instead of
+    /// aborting if the GC heap can't satisfy the allocation.
+    pub(crate) fn try_append_list_element<'gc>(
+        &'gc self,
+        prev: Option<&'gc NodeListElement<'gc>>,
+        node: &'gc Node<'gc>,
+    ) -> Result<&'gc NodeListElement<'gc>, AllocError> {
+        let elem = self
+            .try_alloc(NodeListElement {
+                next: std::cell::Cell::new(std::ptr::null()),
+                inner: node as *const Node<'gc>,
+            })
+            .ok_or(AllocError)?;
+        if let Some(prev) = prev {
+            prev.next.set(elem as *const NodeListElement<'gc>);
+        }
+        Ok(elem)
+    }
+}
+
+impl<'a> NodeList<'a> {
+    /// Fallible counterpart to [`NodeList::from_iter`].
+    ///
+    /// Propagates the first allocation failure. No partially-linked list is
+    /// ever observable: each [`crate::context::NodeListElement`] is only
+    /// reachable from the list once every element up to it has been
+    /// successfully allocated, and the prefix allocated so far remains
+    /// ordinary garbage for the GC to reclaim on the next collection if the
+    /// caller drops the attempt.
+    pub fn try_from_iter<'gc, I: IntoIterator<Item = &'gc Node<'gc>>>(
+        lock: &'gc GCLock<'_, '_>,
+        nodes: I,
+    ) -> Result<NodeList<'gc>, AllocError> {
+        let mut it = nodes.into_iter();
+        let first = match it.next() {
+            Some(first) => first,
+            None => return Ok(NodeList::new(lock)),
+        };
+        let head_elem = lock.try_append_list_element(None, first)?;
+        let mut prev_elem = head_elem;
+        for next in it {
+            prev_elem = lock.try_append_list_element(Some(prev_elem), next)?;
+        }
+        Ok(NodeList { head: head_elem })
+    }
+}
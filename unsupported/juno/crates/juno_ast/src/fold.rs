@@ -0,0 +1,109 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! A bottom-up companion to [`VisitorMut`], modeled on rustc's
+//! `TypeFoldable`/`TypeFolder` split.
+//!
+//! Where `VisitorMut::call` runs top-down and leaves recursion to the
+//! implementor, the default [`Fold::fold_node`] folds every child of a node
+//! before returning it, giving post-order. The recursion itself is driven by
+//! [`super_fold_node`] (mirroring `super_fold_with` in rustc's
+//! `TypeFoldable`/`TypeFolder`): it walks every `NodeChild` of the node via
+//! the generated `visit_children_mut` dispatch, which rebuilds the node
+//! through its builder only if some child actually changed (otherwise the
+//! original is reused, preserving sharing). `Node::fold` is just the entry
+//! point — `folder.fold_node(node)` — so a `Fold` impl that overrides
+//! `fold_node` and wants the ordinary recursion for the cases it doesn't
+//! special-case calls `super_fold_node` itself, same as rustc passes do.
+
+use crate::{GCLock, Node, Path, TransformResult};
+
+/// Trait implemented by bottom-up tree transforms.
+///
+/// Unlike [`VisitorMut`], where the implementor drives recursion explicitly,
+/// a `Fold` impl gets child recursion for free by delegating to
+/// [`super_fold_node`] from its own `fold_node`.
+pub trait Fold<'gc> {
+    /// Called with `node` before any of its children have been folded; this
+    /// default implementation folds them via [`super_fold_node`] and returns
+    /// the result. Passes override this to inspect/rewrite
+    /// nodes of interest, falling back to `super_fold_node` for the rest so
+    /// their children still get visited.
+    fn fold_node(
+        &mut self,
+        ctx: &'gc GCLock,
+        node: &'gc Node<'gc>,
+        path: Option<Path<'gc>>,
+    ) -> TransformResult<&'gc Node<'gc>> {
+        super_fold_node(self, ctx, node, path)
+    }
+}
+
+/// The generic child recursion backing [`Fold::fold_node`]'s default
+/// behavior: fold every `NodeChild` of `node` bottom-up via the generated
+/// `visit_children_mut` dispatch (the same one backing `Node::visit_mut`'s
+/// child recursion), rebuilding `node` through its builder only if at least
+/// one child changed. Implementors call this from their own `fold_node` when
+/// they want this ordinary recursion for cases they don't specifically
+/// handle, mirroring `fold`/`super_fold` in rustc.
+pub fn super_fold_node<'gc, F: Fold<'gc> + ?Sized>(
+    folder: &mut F,
+    ctx: &'gc GCLock,
+    node: &'gc Node<'gc>,
+    _path: Option<Path<'gc>>,
+) -> TransformResult<&'gc Node<'gc>> {
+    let mut child_folder = ChildFolder { folder };
+    node.visit_children_mut(ctx, &mut child_folder)
+}
+
+impl<'gc> Node<'gc> {
+    /// Fold this node bottom-up with `folder`, returning the (possibly
+    /// rebuilt) result, or `None` if the node was removed.
+    ///
+    /// This is just the entry point into `folder.fold_node`; the recursion
+    /// into children happens inside [`super_fold_node`], which the default
+    /// `fold_node` (and any override that wants ordinary recursion) calls.
+    pub fn fold<F: Fold<'gc>>(
+        &'gc self,
+        ctx: &'gc GCLock,
+        folder: &mut F,
+        path: Option<Path<'gc>>,
+    ) -> Option<&'gc Node<'gc>> {
+        match folder.fold_node(ctx, self, path) {
+            TransformResult::Unchanged => Some(self),
+            TransformResult::Removed => None,
+            TransformResult::Changed(new_node) => Some(new_node),
+            TransformResult::Expanded(_) => {
+                panic!("Attempt to replace a single node with multiple during fold");
+            }
+        }
+    }
+}
+
+/// Adapts a [`Fold`] implementor into a [`VisitorMut`] whose `call` folds
+/// whatever child node it's given bottom-up, by recursing back into
+/// [`Node::fold`]. `node.visit_children_mut` (in [`super_fold_node`]) invokes
+/// this once per direct child, each of which recurses into its own children
+/// the same way, giving the whole walk post-order semantics.
+struct ChildFolder<'f, F> {
+    folder: &'f mut F,
+}
+
+impl<'gc, F: Fold<'gc>> crate::VisitorMut<'gc> for ChildFolder<'_, F> {
+    fn call(
+        &mut self,
+        ctx: &'gc GCLock,
+        node: &'gc Node<'gc>,
+        path: Option<Path<'gc>>,
+    ) -> TransformResult<&'gc Node<'gc>> {
+        match node.fold(ctx, self.folder, path) {
+            Some(folded) if std::ptr::eq(folded, node) => TransformResult::Unchanged,
+            Some(folded) => TransformResult::Changed(folded),
+            None => TransformResult::Removed,
+        }
+    }
+}
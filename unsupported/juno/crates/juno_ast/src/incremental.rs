@@ -0,0 +1,296 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Incremental reparsing that reuses most of an existing tree after a small
+//! source edit, instead of reparsing the whole source from scratch.
+//!
+//! Because `&Node` references are `Copy` and immutable under a `GCLock`, any
+//! subtree that lies entirely before the edit is shared by reference into the
+//! new tree. A subtree entirely after the edit keeps its structure but is
+//! rebuilt with its ranges shifted.
+
+use crate::context::{Context, GCLock};
+use crate::fold::{super_fold_node, Fold};
+use crate::{Node, NodeKind, NodeVariant, Path, SourceRange, TransformResult, VisitorMut};
+
+/// A single contiguous edit to a source string, expressed as a byte range
+/// replacement: `old_len` bytes starting at `offset` are replaced by
+/// `new_len` bytes.
+#[derive(Debug, Copy, Clone)]
+pub struct TextEdit {
+    /// Byte offset into the old source where the edit begins.
+    pub offset: u32,
+    /// Number of bytes removed from the old source.
+    pub old_len: u32,
+    /// Number of bytes inserted in their place.
+    pub new_len: u32,
+}
+
+impl TextEdit {
+    fn old_end(&self) -> u32 {
+        self.offset + self.old_len
+    }
+
+    /// How far a range entirely after the edit must be shifted.
+    fn shift(&self) -> i64 {
+        self.new_len as i64 - self.old_len as i64
+    }
+
+    fn entirely_before(&self, range: SourceRange) -> bool {
+        (range.end.offset() as u32) <= self.offset
+    }
+
+    fn entirely_after(&self, range: SourceRange) -> bool {
+        (range.start.offset() as u32) >= self.old_end()
+    }
+
+    /// Whether `range` strictly contains the whole edit.
+    fn contains(&self, range: SourceRange) -> bool {
+        (range.start.offset() as u32) < self.offset
+            && (range.end.offset() as u32) > self.old_end()
+    }
+}
+
+/// Shift `range` by the edit's length delta, for a range known to lie
+/// entirely after the edit.
+fn shifted_range(range: SourceRange, edit: TextEdit) -> SourceRange {
+    let delta = edit.shift();
+    SourceRange {
+        file: range.file,
+        start: range.start.shift(delta),
+        end: range.end.shift(delta),
+    }
+}
+
+/// A node is a valid place to stop descending and reparse in isolation: a
+/// block statement, a function declaration, or the program itself. Reparsing
+/// any of these on its own source slice produces a self-contained, spliceable
+/// subtree.
+fn is_reparsable_boundary(node: &Node) -> bool {
+    matches!(
+        node.variant(),
+        NodeVariant::BlockStatement | NodeVariant::Program | NodeVariant::FunctionDeclaration
+    )
+}
+
+/// Parses a standalone source slice into a `Program` node. This crate has no
+/// parser of its own (that lives in the parser crate, which depends on
+/// `juno_ast` rather than the other way around), so [`Context::reparse`]
+/// takes one of these in rather than assuming a parsing entry point exists
+/// here.
+pub type ParseSource = for<'gc> fn(&'gc GCLock, &str) -> &'gc Node<'gc>;
+
+impl Context {
+    /// Reparse `new_source` starting from `old_root` (parsed from the source
+    /// before `edit` was applied), reusing as much of `old_root` as possible.
+    ///
+    /// Walks down from `old_root` looking for the tightest enclosing
+    /// reparsable boundary (see [`is_reparsable_boundary`]) that strictly
+    /// contains `edit`. Subtrees before the edit are shared by reference with
+    /// `old_root`; subtrees after it are rebuilt with shifted ranges; only
+    /// the boundary's slice of `new_source` is actually reparsed (via
+    /// `parse`) and spliced back in. Falls back to a full reparse of
+    /// `new_source` if `old_root`'s range doesn't strictly contain the edit,
+    /// or if no boundary is found. An edit spanning multiple top-level
+    /// statements without a shared enclosing block makes the program itself
+    /// the boundary, so the program's whole slice is reparsed.
+    pub fn reparse<'gc>(
+        &'gc mut self,
+        old_root: &'gc Node<'gc>,
+        edit: TextEdit,
+        new_source: &str,
+        parse: ParseSource,
+    ) -> &'gc Node<'gc> {
+        let lock = GCLock::new(self);
+        if !edit.contains(old_root.range()) {
+            return parse(&lock, new_source);
+        }
+        match find_reparse_boundary(&lock, old_root, edit) {
+            Some(boundary) => {
+                let rebuilt = reparse_slice(&lock, boundary, edit, new_source, parse);
+                splice(&lock, old_root, boundary, rebuilt, edit)
+            }
+            None => parse(&lock, new_source),
+        }
+    }
+}
+
+/// Find the tightest node reachable from `root` that both strictly contains
+/// `edit` and is a valid reparse boundary.
+fn find_reparse_boundary<'gc>(
+    lock: &'gc GCLock,
+    root: &'gc Node<'gc>,
+    edit: TextEdit,
+) -> Option<&'gc Node<'gc>> {
+    let mut best = is_reparsable_boundary(root).then_some(root);
+    let mut cur = root;
+    while let Some(child) = tightest_child_containing(lock, cur, edit) {
+        if is_reparsable_boundary(child) {
+            best = Some(child);
+        }
+        cur = child;
+    }
+    best
+}
+
+/// Among the direct children of `node`, find the one whose range strictly
+/// contains the edit, if any. There can be at most one, since sibling ranges
+/// are non-overlapping.
+fn tightest_child_containing<'gc>(
+    lock: &'gc GCLock,
+    node: &'gc Node<'gc>,
+    edit: TextEdit,
+) -> Option<&'gc Node<'gc>> {
+    struct Finder<'gc> {
+        edit: TextEdit,
+        found: Option<&'gc Node<'gc>>,
+    }
+    impl<'gc> crate::Visitor<'gc> for Finder<'gc> {
+        fn call(&mut self, _ctx: &'gc GCLock, node: &'gc Node<'gc>, _path: Option<Path<'gc>>) {
+            if self.found.is_none() && self.edit.contains(node.range()) {
+                self.found = Some(node);
+            }
+        }
+    }
+    let mut finder = Finder { edit, found: None };
+    // `visit_children` calls `Finder::call` once per direct child of `node`
+    // (not on `node` itself, and without descending further), which is
+    // exactly "among the direct children" per this function's contract.
+    node.visit_children(lock, &mut finder);
+    finder.found
+}
+
+/// Reparse the slice of `new_source` corresponding to `boundary`'s (shifted)
+/// range in isolation, and return the rebuilt node.
+///
+/// `parse` always produces a `Program`, so unless `boundary` is itself the
+/// program, the statement actually matching `boundary`'s kind is pulled back
+/// out of that program's body before being handed back to the caller.
+///
+/// `parse` has no idea where `new_range` sits inside `new_source` — it only
+/// ever sees the slice — so every range in the node it hands back starts at
+/// (or near) zero. Before this subtree can be spliced back into a tree whose
+/// other ranges are absolute offsets into `new_source`, it needs shifting by
+/// `new_range.start.offset()`, the byte offset where the slice begins.
+fn reparse_slice<'gc>(
+    lock: &'gc GCLock,
+    boundary: &'gc Node<'gc>,
+    edit: TextEdit,
+    new_source: &str,
+    parse: ParseSource,
+) -> &'gc Node<'gc> {
+    let old_range = boundary.range();
+    let new_range = if edit.entirely_after(old_range) {
+        shifted_range(old_range, edit)
+    } else {
+        SourceRange {
+            file: old_range.file,
+            start: old_range.start,
+            end: old_range.end.shift(edit.shift()),
+        }
+    };
+    let slice = &new_source[new_range.start.offset() as usize..new_range.end.offset() as usize];
+    let parsed = parse(lock, slice);
+    let rebuilt = if matches!(boundary.variant(), NodeVariant::Program) {
+        parsed
+    } else {
+        match parsed.kind() {
+            NodeKind::Program(program) => program
+                .body
+                .head()
+                .expect("a reparsed block/function slice parses to a single top-level statement"),
+            _ => unreachable!("ParseSource always returns a Program"),
+        }
+    };
+    shift_node_range(lock, rebuilt, new_range.start.offset() as i64)
+}
+
+/// Rebuild the path from `root` down to `boundary`: every node the path
+/// descends through is reconstructed with the same children, except that the
+/// child on the path to `boundary` is replaced by `rebuilt`, and every
+/// sibling node entirely after the edit has its (and its descendants')
+/// ranges shifted to stay consistent with `new_source`.
+fn splice<'gc>(
+    lock: &'gc GCLock,
+    root: &'gc Node<'gc>,
+    boundary: &'gc Node<'gc>,
+    rebuilt: &'gc Node<'gc>,
+    edit: TextEdit,
+) -> &'gc Node<'gc> {
+    struct Splicer<'gc> {
+        boundary: &'gc Node<'gc>,
+        rebuilt: &'gc Node<'gc>,
+        edit: TextEdit,
+    }
+    impl<'gc> VisitorMut<'gc> for Splicer<'gc> {
+        fn call(
+            &mut self,
+            ctx: &'gc GCLock,
+            node: &'gc Node<'gc>,
+            _path: Option<Path<'gc>>,
+        ) -> TransformResult<&'gc Node<'gc>> {
+            if std::ptr::eq(node, self.boundary) {
+                return TransformResult::Changed(self.rebuilt);
+            }
+            if self.edit.entirely_after(node.range()) {
+                // `boundary`'s range strictly contains the edit, so it can't
+                // live inside a subtree that sits entirely after the edit —
+                // there's nothing left to splice here, just every range in
+                // it needs shifting.
+                return TransformResult::Changed(shift_node_range(ctx, node, self.edit.shift()));
+            }
+            if self.edit.entirely_before(node.range()) {
+                return TransformResult::Unchanged;
+            }
+            // This node overlaps the edit, so it's an ancestor on the way
+            // down to `boundary` (which strictly contains the edit): recurse into
+            // its children so the generated per-kind dispatch can find
+            // `boundary` and rebuild this node through its builder once it
+            // does.
+            node.visit_children_mut(ctx, self)
+        }
+    }
+    let mut splicer = Splicer {
+        boundary,
+        rebuilt,
+        edit,
+    };
+    root.visit_mut(lock, &mut splicer, None).unwrap_or(rebuilt)
+}
+
+/// Rebuild `node` with its own and every descendant's [`SourceRange`] shifted
+/// by `delta` bytes, otherwise unchanged. Used by [`splice`] to keep ranges
+/// accurate for subtrees that sit entirely after an edit, and by
+/// [`reparse_slice`] to move a reparsed subtree to its offset in the new
+/// source.
+fn shift_node_range<'gc>(ctx: &'gc GCLock, node: &'gc Node<'gc>, delta: i64) -> &'gc Node<'gc> {
+    struct RangeShifter {
+        delta: i64,
+    }
+    impl<'gc> Fold<'gc> for RangeShifter {
+        fn fold_node(
+            &mut self,
+            ctx: &'gc GCLock,
+            node: &'gc Node<'gc>,
+            path: Option<Path<'gc>>,
+        ) -> TransformResult<&'gc Node<'gc>> {
+            // Fold children first (so their ranges are already shifted),
+            // then shift this node's own range.
+            let node = match super_fold_node(self, ctx, node, path) {
+                TransformResult::Unchanged => node,
+                TransformResult::Changed(new_node) => new_node,
+                TransformResult::Removed => return TransformResult::Removed,
+                TransformResult::Expanded(_) => {
+                    unreachable!("fold_node never replaces a single node with multiple")
+                }
+            };
+            TransformResult::Changed(node.with_shifted_range(ctx, self.delta))
+        }
+    }
+    node.fold(ctx, &mut RangeShifter { delta }, None)
+        .expect("shifting a range never removes a node")
+}
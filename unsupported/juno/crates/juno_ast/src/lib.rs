@@ -35,20 +35,30 @@ use thiserror::Error;
 #[macro_use]
 mod def;
 
+mod cfg;
 mod context;
 mod dump;
+mod fallible;
 mod field;
+mod fold;
+mod incremental;
 mod kind;
+mod parse;
 mod validate;
 
 pub use juno_support::source_manager::{SourceId, SourceLoc, SourceManager, SourceRange};
 
+pub use cfg::{BasicBlock, BlockId, Cfg, Dominators};
+pub use fallible::AllocError;
 pub use field::NodeField;
+pub use fold::{super_fold_node, Fold};
+pub use incremental::TextEdit;
 pub use kind::NodeVariant;
 
 pub use context::{Context, GCLock, NodePtr, NodeRc};
 pub use dump::{dump_json, Pretty};
 pub use kind::*;
+pub use parse::{parse_json, ParseError};
 pub use validate::{validate_tree, validate_tree_pure, TreeValidationError, ValidationError};
 
 /// Indicates the path to the current node.
@@ -0,0 +1,415 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Reconstruction of a [`Node`] tree from an ESTree-shaped JSON document,
+//! i.e. the inverse of [`crate::dump_json`].
+//!
+//! Each JSON object is dispatched on its `"type"` field to the matching
+//! `builder::*::build_template` call, recursively parsing child objects into
+//! `&Node`, arrays into [`NodeList`], and operator strings back through the
+//! `TryFrom<&str>` impls generated by `define_str_enum!`.
+
+use crate::context::GCLock;
+use crate::{
+    AssignmentExpressionOperator, BinaryExpressionOperator, LogicalExpressionOperator, Node,
+    NodeLabel, NodeList, NodeString, SourceId, SourceLoc, SourceRange, TemplateMetadata,
+    UnaryExpressionOperator, UpdateExpressionOperator, VariableDeclarationKind,
+};
+use juno_support::atom_table::AtomU16;
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+/// An error encountered while reconstructing a [`Node`] tree from JSON.
+#[derive(Debug, Error)]
+pub enum ParseError {
+    /// The top-level document, or a node/child position, was not a JSON object.
+    #[error("expected a JSON object, found {0}")]
+    NotAnObject(String),
+
+    /// A node object was missing its required `"type"` field.
+    #[error("node is missing required field `type`")]
+    MissingType,
+
+    /// The `"type"` field named a node kind this parser does not know how to build.
+    #[error("unknown node type `{0}`")]
+    UnknownType(String),
+
+    /// A node of the given kind was missing a field required by its template.
+    #[error("node of type `{0}` is missing required field `{1}`")]
+    MissingField(String, String),
+
+    /// A field was present but did not have the shape expected for that kind/field.
+    #[error("field `{1}` on node of type `{0}` has an unexpected shape")]
+    InvalidField(String, String),
+
+    /// An operator string did not match any known operator for its field.
+    #[error("invalid operator `{0}` for field `{1}` on node of type `{2}`")]
+    InvalidOperator(String, String, String),
+
+    /// The input was not valid JSON at all.
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+/// Parse an ESTree-shaped JSON document and rebuild it as a `Node` tree
+/// allocated in `lock`'s `Context`.
+///
+/// This is the inverse of [`crate::dump_json`] for the node kinds [`dispatch`]
+/// handles: a dumped document made only of those kinds can be fed back in to
+/// reconstruct the tree. Any other kind yields [`ParseError::UnknownType`].
+/// Some fields are not read back: `Identifier` type annotations and
+/// `optional`, `ExpressionStatement` directives, and `ArrayExpression`
+/// trailing commas take their default values.
+pub fn parse_json<'gc>(lock: &'gc GCLock, json: &[u8]) -> Result<&'gc Node<'gc>, ParseError> {
+    let value: Value = serde_json::from_slice(json)?;
+    parse_node(lock, &value)
+}
+
+/// Parse a single ESTree node object into a `&Node`.
+fn parse_node<'gc>(lock: &'gc GCLock, value: &Value) -> Result<&'gc Node<'gc>, ParseError> {
+    let obj = as_object(value)?;
+    let kind = obj
+        .get("type")
+        .and_then(Value::as_str)
+        .ok_or(ParseError::MissingType)?;
+    let metadata = parse_metadata(lock, obj);
+    dispatch(lock, kind, obj, metadata)
+}
+
+/// Parse the optional `range`/`loc` fields of a node object into `TemplateMetadata`.
+///
+/// `range` (a `[start, end]` byte-offset pair) is preferred when present;
+/// `loc` (a `{start: {line, column}, end: {line, column}}` pair) is used as a
+/// fallback, since some ESTree producers only emit one or the other. If
+/// neither is present or well-formed, the range is left invalid, matching
+/// `TemplateMetadata::default()`.
+fn parse_metadata<'gc>(lock: &'gc GCLock, obj: &Map<String, Value>) -> TemplateMetadata<'gc> {
+    let file = lock.sm().source_id_for_loading();
+    let range = parse_range(file, obj)
+        .or_else(|| parse_loc(lock, file, obj))
+        .unwrap_or(SourceRange {
+            file: SourceId::INVALID,
+            start: SourceLoc::invalid(),
+            end: SourceLoc::invalid(),
+        });
+    TemplateMetadata {
+        phantom: Default::default(),
+        range,
+    }
+}
+
+/// Parse the `range: [start, end]` byte-offset pair, if present and well-formed.
+fn parse_range(file: SourceId, obj: &Map<String, Value>) -> Option<SourceRange> {
+    let range = obj.get("range").and_then(Value::as_array)?;
+    if range.len() != 2 {
+        return None;
+    }
+    let start = range[0].as_u64()?;
+    let end = range[1].as_u64()?;
+    Some(SourceRange {
+        file,
+        start: SourceLoc::from_offset(start as u32),
+        end: SourceLoc::from_offset(end as u32),
+    })
+}
+
+/// Parse the `loc: {start, end}` line/column pair, resolving each position to
+/// a byte offset via the `SourceManager`.
+fn parse_loc<'gc>(
+    lock: &'gc GCLock,
+    file: SourceId,
+    obj: &Map<String, Value>,
+) -> Option<SourceRange> {
+    let loc = obj.get("loc")?.as_object()?;
+    let start = parse_position(lock, file, loc.get("start")?.as_object()?)?;
+    let end = parse_position(lock, file, loc.get("end")?.as_object()?)?;
+    Some(SourceRange { file, start, end })
+}
+
+/// Parse a single ESTree `{line, column}` position (1-based line, 0-based
+/// column) and resolve it to a byte offset in `file`.
+fn parse_position<'gc>(
+    lock: &'gc GCLock,
+    file: SourceId,
+    obj: &Map<String, Value>,
+) -> Option<SourceLoc> {
+    let line = obj.get("line").and_then(Value::as_u64)?;
+    let column = obj.get("column").and_then(Value::as_u64)?;
+    Some(lock.sm().resolve_line_col(file, line as u32, column as u32))
+}
+
+fn as_object(value: &Value) -> Result<&Map<String, Value>, ParseError> {
+    value
+        .as_object()
+        .ok_or_else(|| ParseError::NotAnObject(value.to_string()))
+}
+
+/// Fetch a required child node field and parse it.
+fn req_node<'gc>(
+    lock: &'gc GCLock,
+    kind: &str,
+    obj: &Map<String, Value>,
+    field: &str,
+) -> Result<&'gc Node<'gc>, ParseError> {
+    let child = obj
+        .get(field)
+        .ok_or_else(|| ParseError::MissingField(kind.to_string(), field.to_string()))?;
+    parse_node(lock, child)
+}
+
+/// Fetch an optional child node field (`null` or absent both map to `None`).
+fn opt_node<'gc>(
+    lock: &'gc GCLock,
+    obj: &Map<String, Value>,
+    field: &str,
+) -> Result<Option<&'gc Node<'gc>>, ParseError> {
+    match obj.get(field) {
+        None | Some(Value::Null) => Ok(None),
+        Some(child) => Ok(Some(parse_node(lock, child)?)),
+    }
+}
+
+/// Fetch a required `NodeList` field, parsing each element as a node.
+fn req_list<'gc>(
+    lock: &'gc GCLock,
+    kind: &str,
+    obj: &Map<String, Value>,
+    field: &str,
+) -> Result<NodeList<'gc>, ParseError> {
+    let arr = obj
+        .get(field)
+        .and_then(Value::as_array)
+        .ok_or_else(|| ParseError::MissingField(kind.to_string(), field.to_string()))?;
+    let mut nodes = Vec::with_capacity(arr.len());
+    for elem in arr {
+        nodes.push(parse_node(lock, elem)?);
+    }
+    Ok(NodeList::from_iter(lock, nodes))
+}
+
+/// Fetch a required string field, interning it as a `NodeLabel` (identifier atom).
+fn req_label(
+    lock: &GCLock,
+    kind: &str,
+    obj: &Map<String, Value>,
+    field: &str,
+) -> Result<NodeLabel, ParseError> {
+    let s = obj
+        .get(field)
+        .and_then(Value::as_str)
+        .ok_or_else(|| ParseError::MissingField(kind.to_string(), field.to_string()))?;
+    Ok(lock.atom_table().atom(s))
+}
+
+/// Fetch a required string field, interning it as a UTF-16 `NodeString` literal.
+fn req_string(
+    lock: &GCLock,
+    kind: &str,
+    obj: &Map<String, Value>,
+    field: &str,
+) -> Result<NodeString, ParseError> {
+    let s = obj
+        .get(field)
+        .and_then(Value::as_str)
+        .ok_or_else(|| ParseError::MissingField(kind.to_string(), field.to_string()))?;
+    Ok(AtomU16::from(s.encode_utf16().collect::<Vec<u16>>()))
+}
+
+/// Fetch a required `f64` field.
+fn req_f64(kind: &str, obj: &Map<String, Value>, field: &str) -> Result<f64, ParseError> {
+    obj.get(field)
+        .and_then(Value::as_f64)
+        .ok_or_else(|| ParseError::MissingField(kind.to_string(), field.to_string()))
+}
+
+/// Fetch a required `bool` field.
+fn req_bool(kind: &str, obj: &Map<String, Value>, field: &str) -> Result<bool, ParseError> {
+    obj.get(field)
+        .and_then(Value::as_bool)
+        .ok_or_else(|| ParseError::MissingField(kind.to_string(), field.to_string()))
+}
+
+/// Fetch a required operator string field and parse it via the operator enum's
+/// `TryFrom<&str>` impl.
+fn req_op<'a, T>(kind: &str, obj: &'a Map<String, Value>, field: &str) -> Result<T, ParseError>
+where
+    T: std::convert::TryFrom<&'a str>,
+{
+    let s = obj
+        .get(field)
+        .and_then(Value::as_str)
+        .ok_or_else(|| ParseError::MissingField(kind.to_string(), field.to_string()))?;
+    T::try_from(s).map_err(|_| ParseError::InvalidOperator(s.to_string(), field.to_string(), kind.to_string()))
+}
+
+/// Dispatch on the node-kind string and build the matching node via its
+/// `builder::*::build_template` call.
+///
+/// This covers the common ESTree node kinds; extending it to a new kind is a
+/// matter of adding another arm that reads the relevant fields out of `obj`
+/// and forwards them to that kind's template struct.
+fn dispatch<'gc>(
+    lock: &'gc GCLock,
+    kind: &str,
+    obj: &Map<String, Value>,
+    metadata: TemplateMetadata<'gc>,
+) -> Result<&'gc Node<'gc>, ParseError> {
+    use crate::{builder, template};
+
+    Ok(match kind {
+        "Program" => builder::Program::build_template(
+            lock,
+            template::Program {
+                metadata,
+                body: req_list(lock, kind, obj, "body")?,
+            },
+        ),
+        "Identifier" => builder::Identifier::build_template(
+            lock,
+            template::Identifier {
+                metadata,
+                name: req_label(lock, kind, obj, "name")?,
+                type_annotation: None,
+                optional: false,
+            },
+        ),
+        "NumericLiteral" => builder::NumericLiteral::build_template(
+            lock,
+            template::NumericLiteral {
+                metadata,
+                value: req_f64(kind, obj, "value")?,
+            },
+        ),
+        "StringLiteral" => builder::StringLiteral::build_template(
+            lock,
+            template::StringLiteral {
+                metadata,
+                value: req_string(lock, kind, obj, "value")?,
+            },
+        ),
+        "BooleanLiteral" => builder::BooleanLiteral::build_template(
+            lock,
+            template::BooleanLiteral {
+                metadata,
+                value: req_bool(kind, obj, "value")?,
+            },
+        ),
+        "NullLiteral" => builder::NullLiteral::build_template(lock, template::NullLiteral { metadata }),
+        "EmptyStatement" => {
+            builder::EmptyStatement::build_template(lock, template::EmptyStatement { metadata })
+        }
+        "BlockStatement" => builder::BlockStatement::build_template(
+            lock,
+            template::BlockStatement {
+                metadata,
+                body: req_list(lock, kind, obj, "body")?,
+            },
+        ),
+        "ExpressionStatement" => builder::ExpressionStatement::build_template(
+            lock,
+            template::ExpressionStatement {
+                metadata,
+                expression: req_node(lock, kind, obj, "expression")?,
+                directive: None,
+            },
+        ),
+        "ReturnStatement" => builder::ReturnStatement::build_template(
+            lock,
+            template::ReturnStatement {
+                metadata,
+                argument: opt_node(lock, obj, "argument")?,
+            },
+        ),
+        "IfStatement" => builder::IfStatement::build_template(
+            lock,
+            template::IfStatement {
+                metadata,
+                test: req_node(lock, kind, obj, "test")?,
+                consequent: req_node(lock, kind, obj, "consequent")?,
+                alternate: opt_node(lock, obj, "alternate")?,
+            },
+        ),
+        "UnaryExpression" => builder::UnaryExpression::build_template(
+            lock,
+            template::UnaryExpression {
+                metadata,
+                operator: req_op::<UnaryExpressionOperator>(kind, obj, "operator")?,
+                argument: req_node(lock, kind, obj, "argument")?,
+                prefix: req_bool(kind, obj, "prefix")?,
+            },
+        ),
+        "UpdateExpression" => builder::UpdateExpression::build_template(
+            lock,
+            template::UpdateExpression {
+                metadata,
+                operator: req_op::<UpdateExpressionOperator>(kind, obj, "operator")?,
+                argument: req_node(lock, kind, obj, "argument")?,
+                prefix: req_bool(kind, obj, "prefix")?,
+            },
+        ),
+        "BinaryExpression" => builder::BinaryExpression::build_template(
+            lock,
+            template::BinaryExpression {
+                metadata,
+                operator: req_op::<BinaryExpressionOperator>(kind, obj, "operator")?,
+                left: req_node(lock, kind, obj, "left")?,
+                right: req_node(lock, kind, obj, "right")?,
+            },
+        ),
+        "LogicalExpression" => builder::LogicalExpression::build_template(
+            lock,
+            template::LogicalExpression {
+                metadata,
+                operator: req_op::<LogicalExpressionOperator>(kind, obj, "operator")?,
+                left: req_node(lock, kind, obj, "left")?,
+                right: req_node(lock, kind, obj, "right")?,
+            },
+        ),
+        "AssignmentExpression" => builder::AssignmentExpression::build_template(
+            lock,
+            template::AssignmentExpression {
+                metadata,
+                operator: req_op::<AssignmentExpressionOperator>(kind, obj, "operator")?,
+                left: req_node(lock, kind, obj, "left")?,
+                right: req_node(lock, kind, obj, "right")?,
+            },
+        ),
+        "VariableDeclarator" => builder::VariableDeclarator::build_template(
+            lock,
+            template::VariableDeclarator {
+                metadata,
+                init: opt_node(lock, obj, "init")?,
+                id: req_node(lock, kind, obj, "id")?,
+            },
+        ),
+        "VariableDeclaration" => builder::VariableDeclaration::build_template(
+            lock,
+            template::VariableDeclaration {
+                metadata,
+                kind: req_op::<VariableDeclarationKind>(kind, obj, "kind")?,
+                declarations: req_list(lock, kind, obj, "declarations")?,
+            },
+        ),
+        "CallExpression" => builder::CallExpression::build_template(
+            lock,
+            template::CallExpression {
+                metadata,
+                callee: req_node(lock, kind, obj, "callee")?,
+                arguments: req_list(lock, kind, obj, "arguments")?,
+            },
+        ),
+        "ArrayExpression" => builder::ArrayExpression::build_template(
+            lock,
+            template::ArrayExpression {
+                metadata,
+                elements: req_list(lock, kind, obj, "elements")?,
+                trailing_comma: false,
+            },
+        ),
+        _ => return Err(ParseError::UnknownType(kind.to_string())),
+    })
+}